@@ -0,0 +1,48 @@
+use spacetimedb::{table, ReducerContext, Table};
+
+#[table(name = consumable_effect_stats, public)]
+#[derive(Clone, Debug)]
+pub struct ConsumableEffectStats {
+    #[primary_key]
+    pub item_name: String,   // e.g., "Mushroom", matches ItemDefinition.name
+    pub health_gain: f32,
+    pub hunger_gain: f32,
+    pub thirst_gain: f32,
+    pub consume_duration_secs: f32, // How long the "feeding" action takes before effects apply
+    pub required_tool_item_name: Option<String>, // e.g. "Campfire" for raw meat that must be cooked first
+    pub fun: f32, // Baseline enjoyment multiplier applied to gains, e.g. 1.0 = normal, lower = bland food
+}
+
+/// Seeds the rows that used to be the hardcoded Mushroom/Corn constants in
+/// `consumables::consume_item`, so switching to the data-driven lookup doesn't
+/// silently zero out the effects of the items that already existed.
+///
+/// Not `#[reducer(init)]` itself - a module may only have one `init` lifecycle
+/// reducer, and `survival_stats::init_module` is it. That reducer calls this one.
+pub fn seed_consumable_effect_stats(ctx: &ReducerContext) {
+    let stats = ctx.db.consumable_effect_stats();
+
+    if stats.item_name().find("Mushroom".to_string()).is_none() {
+        stats.insert(ConsumableEffectStats {
+            item_name: "Mushroom".to_string(),
+            health_gain: 5.0,
+            hunger_gain: 10.0,
+            thirst_gain: 5.0,
+            consume_duration_secs: 2.0,
+            required_tool_item_name: None,
+            fun: 1.0,
+        });
+    }
+
+    if stats.item_name().find("Corn".to_string()).is_none() {
+        stats.insert(ConsumableEffectStats {
+            item_name: "Corn".to_string(),
+            health_gain: 15.0,
+            hunger_gain: 25.0,
+            thirst_gain: 10.0,
+            consume_duration_secs: 2.0,
+            required_tool_item_name: None,
+            fun: 1.0,
+        });
+    }
+}
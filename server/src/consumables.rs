@@ -1,5 +1,5 @@
 // server/src/consumables.rs
-use spacetimedb::{ReducerContext, Identity, Table};
+use spacetimedb::{ReducerContext, Identity, Table, TimeDuration, ScheduleAt};
 use log;
 
 // Import table traits needed for ctx.db access
@@ -7,102 +7,435 @@ use crate::player as PlayerTableTrait;
 use crate::items::{InventoryItem, inventory_item as InventoryItemTableTrait};
 use crate::items::{ItemDefinition, item_definition as ItemDefinitionTableTrait};
 use crate::items::ItemCategory; // Import the enum itself
-
-// --- Consumable Effect Constants ---
-const MUSHROOM_HEALTH_GAIN: f32 = 5.0;
-const MUSHROOM_HUNGER_GAIN: f32 = 10.0;
-const MUSHROOM_THIRST_GAIN: f32 = 5.0;
-const CORN_HEALTH_GAIN: f32 = 15.0;     // 3x the health benefit of mushrooms
-const CORN_HUNGER_GAIN: f32 = 25.0;     // More hunger satisfaction than mushrooms
-const CORN_THIRST_GAIN: f32 = 10.0;     // More thirst quenching than mushrooms
+use crate::consumable_effect_stats::consumable_effect_stats as ConsumableEffectStatsTableTrait;
+use crate::consuming_state::{ConsumingState, consuming_state as ConsumingStateTableTrait};
+use crate::recent_consumption::{RecentConsumption, recent_consumption as RecentConsumptionTableTrait};
+use crate::stat_penalty::{StatPenalty, stat_penalty as StatPenaltyTableTrait};
+use crate::overeating_tracker::{OvereatingTracker, overeating_tracker as OvereatingTrackerTableTrait};
 
 // --- Max Stat Value ---
 const MAX_STAT_VALUE: f32 = 100.0; // Max value for health, hunger, thirst
 
-#[spacetimedb::reducer]
-pub fn consume_item(ctx: &ReducerContext, item_instance_id: u64) -> Result<(), String> {
+// Used when an item has no consumable_effect_stats row to take the duration from.
+const DEFAULT_CONSUME_DURATION_SECS: f32 = 2.0;
+
+// --- Diminishing returns for eating the same food repeatedly ---
+const RECENT_CONSUMPTION_WINDOW_SECS: i64 = 600; // 10 minutes; outside this window the count resets
+const REPETITION_PENALTY_PER_REPEAT: f32 = 0.15; // Each repeat within the window knocks off this much
+const MIN_REPETITION_MULTIPLIER: f32 = 0.25; // Floor so a food is never worth nothing
+
+// --- Overconsumption ("engorged") rules ---
+const ENGORGED_REJECT_TOLERANCE: f32 = 15.0; // Hard reject once a bite would overshoot the cap by more than this
+const OVEREAT_THRESHOLD: f32 = MAX_STAT_VALUE - 10.0; // Eating at/above this hunger counts as "overeating"
+const OVEREAT_WINDOW_SECS: i64 = 300; // A gap longer than this resets the overeat streak
+const OVEREAT_REPEAT_LIMIT: u32 = 3; // Streak length that triggers a penalty
+const OVEREAT_PENALTY_MULTIPLIER: f32 = 0.5; // "Stuffed": gains are halved while the penalty is active
+const OVEREAT_PENALTY_DURATION_SECS: i64 = 300;
+
+/// Scheduled completion of a "feeding" action started by `consume_item`.
+/// Fires once per consume attempt; `complete_consume_item` re-checks the
+/// player's `ConsumingState` before applying anything, so a cancelled or
+/// superseded attempt is a harmless no-op when the timer goes off.
+#[spacetimedb::table(name = consume_completion_schedule, scheduled(complete_consume_item))]
+pub struct ConsumeCompletionSchedule {
+    #[primary_key]
+    #[auto_inc]
+    pub schedule_id: u64,
+    pub scheduled_at: ScheduleAt,
+    pub player_identity: Identity,
+    pub item_instance_id: u64,
+    pub started_at: spacetimedb::Timestamp,
+}
+
+/// Cancels a player's in-progress consume, if any, refunding nothing. Called from
+/// `consume_item` when the player switches to eating a different item mid-feed,
+/// from `player_movement::move_player` when they walk away, and from
+/// `player_damage::apply_hazard_damage` when they take damage, so that any of
+/// those interrupts the feeding action the same way.
+pub fn cancel_consuming(ctx: &ReducerContext, player_identity: Identity) {
+    let consuming = ctx.db.consuming_state();
+    if consuming.player_identity().find(player_identity).is_some() {
+        log::debug!("[ConsumeItem] Cancelling pending consume for player {:?}.", player_identity);
+        consuming.player_identity().delete(player_identity);
+    }
+}
+
+/// Why an item can't be eaten right now, as a typed reason rather than a free-form string,
+/// so callers (and the client) can react to specific cases instead of pattern-matching text.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConsumeError {
+    NotOwned,
+    NotConsumable,
+    RequiresTool(String),
+    AlreadyFull,
+    Forbidden(String),
+}
+
+impl std::fmt::Display for ConsumeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConsumeError::NotOwned => write!(f, "Cannot consume an item that does not belong to you."),
+            ConsumeError::NotConsumable => write!(f, "That item is not consumable."),
+            ConsumeError::RequiresTool(tool_name) => write!(f, "You need a {} to eat this.", tool_name),
+            ConsumeError::AlreadyFull => write!(f, "You are already full."),
+            ConsumeError::Forbidden(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+/// Checks whether `item_instance_id` can be eaten by the caller right now, without
+/// mutating any state. `consume_item` calls this before starting a feeding action.
+pub fn can_consume(ctx: &ReducerContext, item_instance_id: u64) -> Result<(), ConsumeError> {
     let sender_id = ctx.sender;
     let inventory = ctx.db.inventory_item();
     let item_defs = ctx.db.item_definition();
     let players = ctx.db.player();
+    let effect_stats = ctx.db.consumable_effect_stats();
+    let consuming = ctx.db.consuming_state();
 
-    log::info!("[ConsumeItem] Player {:?} attempting to consume item instance {}", sender_id, item_instance_id);
+    // 1 & 2. Item must exist and belong to the caller.
+    let item_to_consume = inventory.instance_id().find(item_instance_id)
+        .ok_or(ConsumeError::NotOwned)?;
+    if item_to_consume.player_identity != sender_id {
+        return Err(ConsumeError::NotOwned);
+    }
 
-    // 1. Get the InventoryItem being consumed
-    let mut item_to_consume = inventory.instance_id().find(item_instance_id)
-        .ok_or_else(|| format!("Item instance {} not found.", item_instance_id))?;
+    // 3 & 4. Item must be a known, Consumable item.
+    let item_def = item_defs.id().find(item_to_consume.item_def_id)
+        .ok_or(ConsumeError::NotConsumable)?;
+    if item_def.category != ItemCategory::Consumable {
+        return Err(ConsumeError::NotConsumable);
+    }
 
-    // 2. Verify ownership
-    if item_to_consume.player_identity != sender_id {
-        return Err("Cannot consume an item that does not belong to you.".to_string());
+    // 5. Calling consume_item again for the exact item already being consumed is
+    // rejected as redundant; switching to a *different* item is allowed and cancels the old
+    // feeding action (see the switch-over in `consume_item`).
+    if let Some(existing) = consuming.player_identity().find(sender_id) {
+        if existing.item_instance_id == item_instance_id {
+            return Err(ConsumeError::Forbidden("Already consuming this item.".to_string()));
+        }
     }
 
-    // 3. Get its ItemDefinition
+    let stats = effect_stats.item_name().find(item_def.name.clone());
+
+    // 6. Some foods need a tool present in the inventory (e.g. raw meat needs a cooking tool).
+    if let Some(tool_name) = stats.as_ref().and_then(|s| s.required_tool_item_name.clone()) {
+        let has_tool = inventory.iter().any(|inv_item| {
+            inv_item.player_identity == sender_id
+                && item_defs.id().find(inv_item.item_def_id)
+                    .map_or(false, |def| def.name == tool_name)
+        });
+        if !has_tool {
+            return Err(ConsumeError::RequiresTool(tool_name));
+        }
+    }
+
+    // 7. Engorged: reject outright if this bite would overshoot the cap by more than a
+    // small tolerance. Uses the same fun/repetition/penalty multiplier complete_consume_item
+    // will actually apply, not the raw table value, so a stale/bland repeat bite isn't
+    // rejected for overshooting a cap it could never have reached.
+    if let Some(stats) = stats.as_ref() {
+        if stats.hunger_gain > 0.0 {
+            let player = players.identity().find(sender_id)
+                .ok_or_else(|| ConsumeError::Forbidden("Player not found.".to_string()))?;
+            let now = ctx.timestamp;
+            let repetition_multiplier = repetition_multiplier_from_count(
+                peek_recent_consumption_count(ctx, sender_id, &item_def.name, now)
+            );
+            // Read-only counterpart of active_penalty_multiplier: an expired penalty counts as
+            // 1.0 here but is left in place for the real completion to clean up.
+            let penalty_multiplier = ctx.db.stat_penalty().player_identity().find(sender_id)
+                .filter(|penalty| penalty.expires_at > now)
+                .map_or(1.0, |penalty| penalty.gain_multiplier);
+            let effective_hunger_gain = stats.hunger_gain * stats.fun * repetition_multiplier * penalty_multiplier;
+            if player.hunger + effective_hunger_gain > MAX_STAT_VALUE + ENGORGED_REJECT_TOLERANCE {
+                return Err(ConsumeError::AlreadyFull);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[spacetimedb::reducer]
+pub fn consume_item(ctx: &ReducerContext, item_instance_id: u64) -> Result<(), String> {
+    let sender_id = ctx.sender;
+
+    can_consume(ctx, item_instance_id).map_err(|e| e.to_string())?;
+
+    log::info!("[ConsumeItem] Player {:?} attempting to consume item instance {}", sender_id, item_instance_id);
+
+    let inventory = ctx.db.inventory_item();
+    let item_defs = ctx.db.item_definition();
+    let effect_stats = ctx.db.consumable_effect_stats();
+    let consuming = ctx.db.consuming_state();
+
+    // can_consume already validated these, so the lookups here cannot fail.
+    let item_to_consume = inventory.instance_id().find(item_instance_id)
+        .ok_or_else(|| format!("Item instance {} not found.", item_instance_id))?;
     let item_def = item_defs.id().find(item_to_consume.item_def_id)
         .ok_or_else(|| format!("Definition not found for item ID {}", item_to_consume.item_def_id))?;
 
-    // 4. Validate: Must be Consumable category
-    if item_def.category != ItemCategory::Consumable {
-        return Err(format!("Item '{}' is not consumable.", item_def.name));
+    // Look up the duration for this food; items with no data row still take the default time.
+    let duration_secs = effect_stats.item_name().find(item_def.name.clone())
+        .map(|stats| stats.consume_duration_secs)
+        .unwrap_or(DEFAULT_CONSUME_DURATION_SECS);
+
+    let started_at = ctx.timestamp;
+
+    // Switching to a different item mid-feed cancels whatever was being eaten before;
+    // can_consume only let this through when it's a different item_instance_id.
+    if consuming.player_identity().find(sender_id).is_some() {
+        log::debug!("[ConsumeItem] Player {:?} switched items mid-feed; cancelling previous consume.", sender_id);
+        cancel_consuming(ctx, sender_id);
     }
 
-    // 5. Find the player to apply effects to
-    let mut player = players.identity().find(sender_id)
-        .ok_or_else(|| "Player not found to apply consumable effects.".to_string())?;
+    consuming.insert(ConsumingState {
+        player_identity: sender_id,
+        item_instance_id,
+        started_at,
+        duration_secs,
+    });
+
+    let fire_at = started_at + TimeDuration::from_micros((duration_secs * 1_000_000.0) as i64);
+    ctx.db.consume_completion_schedule().insert(ConsumeCompletionSchedule {
+        schedule_id: 0,
+        scheduled_at: fire_at.into(),
+        player_identity: sender_id,
+        item_instance_id,
+        started_at,
+    });
+
+    log::debug!(
+        "[ConsumeItem] Player {:?} started consuming item instance {} ({:.1}s).",
+        sender_id, item_instance_id, duration_secs
+    );
+
+    Ok(())
+}
+
+/// Pure curve from "how many times has this been eaten in the window" to the
+/// multiplier applied to its gains. Shared by the read-only preview in
+/// `can_consume` and the real update in `record_and_score_consumption`, so the
+/// two can never disagree about what a given count is worth.
+fn repetition_multiplier_from_count(count_in_window: u32) -> f32 {
+    (1.0 - REPETITION_PENALTY_PER_REPEAT * (count_in_window.saturating_sub(1) as f32)).max(MIN_REPETITION_MULTIPLIER)
+}
+
+/// What `count_in_window` would become if `item_name` were eaten by `player_identity`
+/// right now, without writing anything. Used to preview the repetition multiplier
+/// before the feeding action actually starts.
+fn peek_recent_consumption_count(ctx: &ReducerContext, player_identity: Identity, item_name: &str, now: spacetimedb::Timestamp) -> u32 {
+    match ctx.db.recent_consumption().iter().find(|r| r.player_identity == player_identity && r.item_name == item_name) {
+        Some(row) => {
+            let elapsed_micros = (now - row.last_eaten_at).to_micros();
+            let within_window = elapsed_micros >= 0 && elapsed_micros <= RECENT_CONSUMPTION_WINDOW_SECS * 1_000_000;
+            if within_window { row.count_in_window + 1 } else { 1 }
+        }
+        None => 1,
+    }
+}
+
+/// Records that `item_name` was just eaten by `player_identity` and returns the
+/// multiplier to apply to its gains: repeated eating within the recent-consumption
+/// window tastes worse each time, recovering once the window has passed.
+fn record_and_score_consumption(ctx: &ReducerContext, player_identity: Identity, item_name: &str, eaten_at: spacetimedb::Timestamp) -> f32 {
+    let recent = ctx.db.recent_consumption();
+    let existing = recent.iter().find(|r| r.player_identity == player_identity && r.item_name == item_name);
+    let count_in_window = peek_recent_consumption_count(ctx, player_identity, item_name, eaten_at);
+
+    match existing {
+        Some(row) => {
+            recent.id().update(RecentConsumption {
+                id: row.id,
+                player_identity,
+                item_name: item_name.to_string(),
+                last_eaten_at: eaten_at,
+                count_in_window,
+            });
+        }
+        None => {
+            recent.insert(RecentConsumption {
+                id: 0,
+                player_identity,
+                item_name: item_name.to_string(),
+                last_eaten_at: eaten_at,
+                count_in_window,
+            });
+        }
+    };
+
+    repetition_multiplier_from_count(count_in_window)
+}
+
+/// Returns the gain multiplier from any active `StatPenalty` on the player (1.0 if
+/// none, or if a stale one has expired - in which case it's cleaned up here).
+fn active_penalty_multiplier(ctx: &ReducerContext, player_identity: Identity, now: spacetimedb::Timestamp) -> f32 {
+    let penalties = ctx.db.stat_penalty();
+    match penalties.player_identity().find(player_identity) {
+        Some(penalty) if penalty.expires_at > now => penalty.gain_multiplier,
+        Some(_) => {
+            penalties.player_identity().delete(player_identity);
+            1.0
+        }
+        None => 1.0,
+    }
+}
+
+/// Tracks eating while already close to full. A streak of `OVEREAT_REPEAT_LIMIT`
+/// such bites within `OVEREAT_WINDOW_SECS` of each other applies a temporary
+/// "stuffed" `StatPenalty`, instead of letting overeating be a harmless no-op.
+fn record_overeat_if_applicable(ctx: &ReducerContext, player_identity: Identity, hunger_before_gain: f32, now: spacetimedb::Timestamp) {
+    if hunger_before_gain < OVEREAT_THRESHOLD {
+        return;
+    }
+
+    let trackers = ctx.db.overeating_tracker();
+    let new_count = match trackers.player_identity().find(player_identity) {
+        Some(tracker) => {
+            let elapsed_micros = (now - tracker.last_overeat_at).to_micros();
+            let within_window = elapsed_micros >= 0 && elapsed_micros <= OVEREAT_WINDOW_SECS * 1_000_000;
+            let new_count = if within_window { tracker.recent_overeat_count + 1 } else { 1 };
+            trackers.player_identity().update(OvereatingTracker {
+                player_identity,
+                recent_overeat_count: new_count,
+                last_overeat_at: now,
+            });
+            new_count
+        }
+        None => {
+            trackers.insert(OvereatingTracker {
+                player_identity,
+                recent_overeat_count: 1,
+                last_overeat_at: now,
+            });
+            1
+        }
+    };
+
+    if new_count >= OVEREAT_REPEAT_LIMIT {
+        log::info!("[ConsumeItem] Player {:?} overate {} times in a row; applying stuffed penalty.", player_identity, new_count);
+        let penalties = ctx.db.stat_penalty();
+        let expires_at = now + TimeDuration::from_micros(OVEREAT_PENALTY_DURATION_SECS * 1_000_000);
+        let penalty = StatPenalty {
+            player_identity,
+            gain_multiplier: OVEREAT_PENALTY_MULTIPLIER,
+            applied_at: now,
+            expires_at,
+        };
+        if penalties.player_identity().find(player_identity).is_some() {
+            penalties.player_identity().update(penalty);
+        } else {
+            penalties.insert(penalty);
+        }
+        trackers.player_identity().delete(player_identity);
+    }
+}
+
+#[spacetimedb::reducer]
+pub fn complete_consume_item(ctx: &ReducerContext, args: ConsumeCompletionSchedule) -> Result<(), String> {
+    // This reducer trusts `args.player_identity` instead of `ctx.sender`, so it must only
+    // ever run as a scheduled callback - otherwise any client could force-complete another
+    // player's feeding action by calling it directly with a row read off the public tables.
+    if ctx.sender != ctx.identity() {
+        return Err("complete_consume_item may only be invoked by the scheduler.".to_string());
+    }
+
+    let sender_id = args.player_identity;
+    let consuming = ctx.db.consuming_state();
+
+    // If the pending state is gone, or belongs to a newer/different attempt, the consume
+    // was interrupted (damage, movement, item switch, etc.) - do nothing, refund nothing.
+    let Some(state) = consuming.player_identity().find(sender_id) else {
+        log::debug!("[ConsumeItem] Completion fired for player {:?} with no pending consume; ignoring.", sender_id);
+        return Ok(());
+    };
+    if state.item_instance_id != args.item_instance_id || state.started_at != args.started_at {
+        log::debug!("[ConsumeItem] Completion fired for a superseded consume by player {:?}; ignoring.", sender_id);
+        return Ok(());
+    }
+
+    let inventory = ctx.db.inventory_item();
+    let item_defs = ctx.db.item_definition();
+    let players = ctx.db.player();
+    let effect_stats = ctx.db.consumable_effect_stats();
+
+    // The item or player may have vanished mid-consume (traded away, disconnected, etc.).
+    let Some(mut item_to_consume) = inventory.instance_id().find(args.item_instance_id) else {
+        consuming.player_identity().delete(sender_id);
+        return Ok(());
+    };
+    let Some(item_def) = item_defs.id().find(item_to_consume.item_def_id) else {
+        consuming.player_identity().delete(sender_id);
+        return Ok(());
+    };
+    let Some(mut player) = players.identity().find(sender_id) else {
+        consuming.player_identity().delete(sender_id);
+        return Ok(());
+    };
 
-    // 6. Apply Effects (Based on item type)
-    let mut stat_changed = false;
-    
-    // Get initial stats for logging
     let old_health = player.health;
     let old_hunger = player.hunger;
     let old_thirst = player.thirst;
-    
-    // Apply effects based on item name
-    match item_def.name.as_str() {
-        "Mushroom" => {
-            player.health = (player.health + MUSHROOM_HEALTH_GAIN).min(MAX_STAT_VALUE);
-            player.hunger = (player.hunger + MUSHROOM_HUNGER_GAIN).min(MAX_STAT_VALUE);
-            player.thirst = (player.thirst + MUSHROOM_THIRST_GAIN).min(MAX_STAT_VALUE);
-            stat_changed = true;
-        },
-        "Corn" => {
-            player.health = (player.health + CORN_HEALTH_GAIN).min(MAX_STAT_VALUE);
-            player.hunger = (player.hunger + CORN_HUNGER_GAIN).min(MAX_STAT_VALUE);
-            player.thirst = (player.thirst + CORN_THIRST_GAIN).min(MAX_STAT_VALUE);
+
+    let mut stat_changed = false;
+    match effect_stats.item_name().find(item_def.name.clone()) {
+        Some(stats) => {
+            let hunger_before_gain = player.hunger;
+            let repetition_multiplier = record_and_score_consumption(ctx, sender_id, &item_def.name, ctx.timestamp);
+            let penalty_multiplier = active_penalty_multiplier(ctx, sender_id, ctx.timestamp);
+            let multiplier = stats.fun * repetition_multiplier * penalty_multiplier;
+            player.health = (player.health + stats.health_gain * multiplier).min(MAX_STAT_VALUE);
+            player.hunger = (player.hunger + stats.hunger_gain * multiplier).min(MAX_STAT_VALUE);
+            player.thirst = (player.thirst + stats.thirst_gain * multiplier).min(MAX_STAT_VALUE);
             stat_changed = true;
+            record_overeat_if_applicable(ctx, sender_id, hunger_before_gain, ctx.timestamp);
         },
-        _ => {
+        None => {
             log::warn!("[ConsumeItem] Consumed item '{}' has no defined effect.", item_def.name);
-            // Return Ok even if no effect, item is still consumed
         }
     }
-    
-    // Log stat changes if any occurred
+
     if stat_changed {
         log::info!(
-            "[ConsumeItem] Player {:?} consumed {}. Stats: H {:.1}->{:.1}, Hu {:.1}->{:.1}, T {:.1}->{:.1}",
-            sender_id, item_def.name, 
-            old_health, player.health, 
-            old_hunger, player.hunger, 
+            "[ConsumeItem] Player {:?} finished consuming {}. Stats: H {:.1}->{:.1}, Hu {:.1}->{:.1}, T {:.1}->{:.1}",
+            sender_id, item_def.name,
+            old_health, player.health,
+            old_hunger, player.hunger,
             old_thirst, player.thirst
         );
     }
 
-    // 7. Decrease quantity or delete item stack
     item_to_consume.quantity -= 1;
     if item_to_consume.quantity == 0 {
-        log::debug!("[ConsumeItem] Item instance {} stack depleted, deleting.", item_instance_id);
-        inventory.instance_id().delete(item_instance_id);
+        log::debug!("[ConsumeItem] Item instance {} stack depleted, deleting.", args.item_instance_id);
+        inventory.instance_id().delete(args.item_instance_id);
     } else {
-        log::debug!("[ConsumeItem] Item instance {} quantity reduced to {}.", item_instance_id, item_to_consume.quantity);
+        log::debug!("[ConsumeItem] Item instance {} quantity reduced to {}.", args.item_instance_id, item_to_consume.quantity);
         inventory.instance_id().update(item_to_consume);
     }
 
-    // 8. Update Player state only if stats changed
     if stat_changed {
-         players.identity().update(player);
+        players.identity().update(player);
     }
 
+    consuming.player_identity().delete(sender_id);
+
     Ok(())
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repetition_multiplier_curve() {
+        // First bite in a window is full value.
+        assert_eq!(repetition_multiplier_from_count(1), 1.0);
+        // Each repeat knocks off REPETITION_PENALTY_PER_REPEAT.
+        assert!((repetition_multiplier_from_count(2) - 0.85).abs() < 1e-5);
+        assert!((repetition_multiplier_from_count(3) - 0.70).abs() < 1e-5);
+        // The curve bottoms out at MIN_REPETITION_MULTIPLIER and never goes lower.
+        assert_eq!(repetition_multiplier_from_count(100), MIN_REPETITION_MULTIPLIER);
+    }
+}
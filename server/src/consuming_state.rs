@@ -0,0 +1,15 @@
+use spacetimedb::{table, Identity, Timestamp};
+
+/// Tracks an in-progress "feeding" action for a player. Presence of a row
+/// means the player has a pending consume that will resolve once
+/// `consume_completion_schedule` fires; deleting the row before then
+/// cancels it (see `consumables::cancel_consuming`).
+#[table(name = consuming_state, public)]
+#[derive(Clone, Debug)]
+pub struct ConsumingState {
+    #[primary_key]
+    pub player_identity: Identity,
+    pub item_instance_id: u64,
+    pub started_at: Timestamp,
+    pub duration_secs: f32,
+}
@@ -0,0 +1,13 @@
+use spacetimedb::{table, Identity, Timestamp};
+
+/// Counts how many times in a row a player has eaten while already close to
+/// full on hunger. Repeated overeating within the window escalates into a
+/// `StatPenalty`; a gap longer than the window resets the count.
+#[table(name = overeating_tracker, public)]
+#[derive(Clone, Debug)]
+pub struct OvereatingTracker {
+    #[primary_key]
+    pub player_identity: Identity,
+    pub recent_overeat_count: u32,
+    pub last_overeat_at: Timestamp,
+}
@@ -0,0 +1,35 @@
+use spacetimedb::{reducer, ReducerContext, Table};
+use log;
+
+use crate::player as PlayerTableTrait;
+use crate::consumables::cancel_consuming;
+
+const MIN_STAT_VALUE: f32 = 0.0;
+
+/// Applies environmental/hazard damage (fall damage, fire, drowning, etc.) to the
+/// caller. Combat damage from other players/entities belongs in a dedicated combat
+/// module once one exists - see the TODO in `fire_ranged_weapon`.
+///
+/// Taking damage cancels whatever the player was feeding on, the same as switching
+/// items does in `consume_item`.
+#[reducer]
+pub fn apply_hazard_damage(ctx: &ReducerContext, amount: f32) -> Result<(), String> {
+    if amount < 0.0 {
+        return Err("Damage amount cannot be negative.".to_string());
+    }
+
+    let sender_id = ctx.sender;
+    let players = ctx.db.player();
+
+    let mut player = players.identity().find(sender_id)
+        .ok_or_else(|| "Player not found.".to_string())?;
+
+    player.health = (player.health - amount).max(MIN_STAT_VALUE);
+    players.identity().update(player);
+
+    cancel_consuming(ctx, sender_id);
+
+    log::debug!("[ApplyHazardDamage] Player {:?} took {:.1} damage.", sender_id, amount);
+
+    Ok(())
+}
@@ -0,0 +1,26 @@
+use spacetimedb::{reducer, ReducerContext, Table};
+use log;
+
+use crate::player as PlayerTableTrait;
+use crate::consumables::cancel_consuming;
+
+/// Moves the caller to a new position. Walking away cancels whatever they were
+/// feeding on, the same as switching items does in `consume_item`.
+#[reducer]
+pub fn move_player(ctx: &ReducerContext, new_x: f32, new_y: f32) -> Result<(), String> {
+    let sender_id = ctx.sender;
+    let players = ctx.db.player();
+
+    let mut player = players.identity().find(sender_id)
+        .ok_or_else(|| "Player not found.".to_string())?;
+
+    player.position_x = new_x;
+    player.position_y = new_y;
+    players.identity().update(player);
+
+    cancel_consuming(ctx, sender_id);
+
+    log::debug!("[MovePlayer] Player {:?} moved to ({:.1}, {:.1}).", sender_id, new_x, new_y);
+
+    Ok(())
+}
@@ -0,0 +1,11 @@
+use spacetimedb::{table, Identity, Timestamp};
+
+/// Tracks when a player last fired a ranged weapon, so `fire_ranged_weapon`
+/// can enforce `RangedWeaponStats::reload_time_secs` between shots.
+#[table(name = ranged_weapon_cooldown, public)]
+#[derive(Clone, Debug)]
+pub struct RangedWeaponCooldown {
+    #[primary_key]
+    pub player_identity: Identity,
+    pub last_fired_at: Timestamp,
+}
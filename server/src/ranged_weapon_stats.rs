@@ -1,4 +1,11 @@
-use spacetimedb::{table, SpacetimeType, Timestamp};
+use spacetimedb::{table, reducer, ReducerContext, Table, SpacetimeType, Timestamp};
+use log;
+use rand::Rng;
+
+use crate::player as PlayerTableTrait;
+use crate::items::{inventory_item as InventoryItemTableTrait};
+use crate::items::{item_definition as ItemDefinitionTableTrait};
+use crate::ranged_weapon_cooldown::{RangedWeaponCooldown, ranged_weapon_cooldown as RangedWeaponCooldownTableTrait};
 
 // #[derive(SpacetimeType, Clone, Debug)] // Remove this if #[table] is used, or ensure SpacetimeType is not re-derived
 #[table(name = ranged_weapon_stats, public)] // Use identifier, not string
@@ -10,5 +17,130 @@ pub struct RangedWeaponStats {
     pub projectile_speed: f32,      // Speed in world units per second
     pub accuracy: f32,              // Value between 0.0 (wildly inaccurate) and 1.0 (perfectly accurate)
     pub reload_time_secs: f32,      // Time between shots
-    // pub ammo_item_def_id: Option<u64>, // Future: if different ammo types are used
-} 
\ No newline at end of file
+    pub ammo_item_def_id: Option<u64>, // If set, firing consumes one matching InventoryItem as ammo
+}
+
+/// Hit/miss result of a `fire_ranged_weapon` shot. It is written to the server log
+/// only; the reducer neither stores it in a table nor returns it to the client.
+#[derive(SpacetimeType, Clone, Debug)]
+pub enum FireOutcome {
+    Hit,
+    Miss,
+}
+
+/// Chance of landing a hit at `distance` with a weapon of the given `accuracy`
+/// and `weapon_range`: accuracy falls off linearly with distance, down to half
+/// of `accuracy` at (or beyond) max range.
+fn hit_chance_for(accuracy: f32, distance: f32, weapon_range: f32) -> f32 {
+    let falloff = 1.0 - (distance / weapon_range).clamp(0.0, 1.0) * 0.5;
+    (accuracy * falloff).clamp(0.0, 1.0)
+}
+
+#[reducer]
+pub fn fire_ranged_weapon(ctx: &ReducerContext, target_x: f32, target_y: f32) -> Result<(), String> {
+    let sender_id = ctx.sender;
+    let players = ctx.db.player();
+    let inventory = ctx.db.inventory_item();
+    let item_defs = ctx.db.item_definition();
+    let weapon_stats = ctx.db.ranged_weapon_stats();
+    let cooldowns = ctx.db.ranged_weapon_cooldown();
+
+    let player = players.identity().find(sender_id)
+        .ok_or_else(|| "Player not found.".to_string())?;
+
+    // 1. Find the player's equipped ranged weapon and its stats.
+    let equipped_weapon = inventory.iter()
+        .find(|inv_item| inv_item.player_identity == sender_id && inv_item.is_equipped)
+        .ok_or_else(|| "No weapon equipped.".to_string())?;
+    let weapon_def = item_defs.id().find(equipped_weapon.item_def_id)
+        .ok_or_else(|| format!("Definition not found for item ID {}", equipped_weapon.item_def_id))?;
+    let stats = weapon_stats.item_name().find(weapon_def.name.clone())
+        .ok_or_else(|| format!("'{}' is not a ranged weapon.", weapon_def.name))?;
+
+    // 2. Enforce reload_time_secs via the player's last-fired timestamp.
+    let now = ctx.timestamp;
+    if let Some(cooldown) = cooldowns.player_identity().find(sender_id) {
+        let elapsed_micros = (now - cooldown.last_fired_at).to_micros();
+        let reload_micros = (stats.reload_time_secs * 1_000_000.0) as i64;
+        if elapsed_micros < reload_micros {
+            return Err(format!(
+                "Weapon is reloading ({:.1}s left).",
+                (reload_micros - elapsed_micros) as f32 / 1_000_000.0
+            ));
+        }
+    }
+
+    // 3. Check the target is within weapon_range.
+    let dx = target_x - player.position_x;
+    let dy = target_y - player.position_y;
+    let distance = (dx * dx + dy * dy).sqrt();
+    if distance > stats.weapon_range {
+        return Err(format!("Target is out of range ({:.1} > {:.1}).", distance, stats.weapon_range));
+    }
+
+    // 4. Consume one unit of ammo, if this weapon requires it.
+    if let Some(ammo_item_def_id) = stats.ammo_item_def_id {
+        let mut ammo_item = inventory.iter()
+            .find(|inv_item| inv_item.player_identity == sender_id && inv_item.item_def_id == ammo_item_def_id)
+            .ok_or_else(|| "Out of ammo.".to_string())?;
+        ammo_item.quantity -= 1;
+        if ammo_item.quantity == 0 {
+            inventory.instance_id().delete(ammo_item.instance_id);
+        } else {
+            inventory.instance_id().update(ammo_item);
+        }
+    }
+
+    // 5. Resolve the hit: accuracy falls off linearly with distance across the weapon's range.
+    let hit_chance = hit_chance_for(stats.accuracy, distance, stats.weapon_range);
+    let roll: f32 = ctx.rng().gen_range(0.0..1.0);
+    let outcome = if roll <= hit_chance { FireOutcome::Hit } else { FireOutcome::Miss };
+
+    log::info!(
+        "[FireRangedWeapon] Player {:?} fired {} at ({:.1}, {:.1}), distance {:.1}, hit_chance {:.2}, roll {:.2} -> {:?}",
+        sender_id, weapon_def.name, target_x, target_y, distance, hit_chance, roll, outcome
+    );
+
+    // TODO: once a combat/damage module exists, apply damage to whatever is at (target_x, target_y) on Hit.
+
+    match cooldowns.player_identity().find(sender_id) {
+        Some(_) => {
+            cooldowns.player_identity().update(RangedWeaponCooldown { player_identity: sender_id, last_fired_at: now });
+        }
+        None => {
+            cooldowns.insert(RangedWeaponCooldown { player_identity: sender_id, last_fired_at: now });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hit_chance_at_point_blank_is_full_accuracy() {
+        assert_eq!(hit_chance_for(0.8, 0.0, 20.0), 0.8);
+    }
+
+    #[test]
+    fn hit_chance_at_max_range_is_halved() {
+        assert!((hit_chance_for(0.8, 20.0, 20.0) - 0.4).abs() < 1e-5);
+    }
+
+    #[test]
+    fn hit_chance_beyond_range_clamps_to_the_max_range_value() {
+        assert_eq!(hit_chance_for(0.8, 40.0, 20.0), hit_chance_for(0.8, 20.0, 20.0));
+    }
+
+    #[test]
+    fn zero_accuracy_never_hits() {
+        assert_eq!(hit_chance_for(0.0, 0.0, 20.0), 0.0);
+    }
+
+    #[test]
+    fn hit_chance_is_clamped_to_one() {
+        assert_eq!(hit_chance_for(1.5, 0.0, 20.0), 1.0);
+    }
+}
@@ -0,0 +1,16 @@
+use spacetimedb::{table, Identity, Timestamp};
+
+/// Tracks how recently and how often a player has eaten a given food, so
+/// `complete_consume_item` can apply a repetition penalty to the gains
+/// (eating the same thing over and over tastes worse each time).
+#[table(name = recent_consumption, public)]
+#[derive(Clone, Debug)]
+pub struct RecentConsumption {
+    #[primary_key]
+    #[auto_inc]
+    pub id: u64,
+    pub player_identity: Identity,
+    pub item_name: String,
+    pub last_eaten_at: Timestamp,
+    pub count_in_window: u32,
+}
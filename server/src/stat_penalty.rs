@@ -0,0 +1,14 @@
+use spacetimedb::{table, Identity, Timestamp};
+
+/// A short-lived negative status. Currently applied after repeated overeating;
+/// while active, `gain_multiplier` further scales down the benefit of any
+/// consumable eaten, on top of the repetition and "fun" multipliers.
+#[table(name = stat_penalty, public)]
+#[derive(Clone, Debug)]
+pub struct StatPenalty {
+    #[primary_key]
+    pub player_identity: Identity,
+    pub gain_multiplier: f32,
+    pub applied_at: Timestamp,
+    pub expires_at: Timestamp,
+}
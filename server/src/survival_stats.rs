@@ -0,0 +1,186 @@
+use spacetimedb::{table, Identity, ReducerContext, Table, TimeDuration, ScheduleAt, SpacetimeType};
+use log;
+
+use crate::player as PlayerTableTrait;
+use crate::consumable_effect_stats::seed_consumable_effect_stats;
+
+// --- Decay rates, per tick ---
+const HUNGER_DECAY_PER_TICK: f32 = 1.0;
+const THIRST_DECAY_PER_TICK: f32 = 1.5;
+const TICK_INTERVAL_SECS: i64 = 60; // one tick per in-game minute
+
+// --- Tier thresholds ---
+const HUNGER_WELL_FED_THRESHOLD: f32 = 80.0;
+const HUNGER_HUNGRY_THRESHOLD: f32 = 40.0;
+const HUNGER_STARVING_THRESHOLD: f32 = 10.0;
+
+const THIRST_HYDRATED_THRESHOLD: f32 = 80.0;
+const THIRST_THIRSTY_THRESHOLD: f32 = 40.0;
+const THIRST_DEHYDRATED_THRESHOLD: f32 = 10.0;
+
+// --- Health effects of the tiers ---
+const STARVATION_HEALTH_DRAIN_PER_TICK: f32 = 2.0;
+const DEHYDRATION_HEALTH_DRAIN_PER_TICK: f32 = 2.0;
+const WELL_FED_HEALTH_REGEN_PER_TICK: f32 = 1.0;
+
+const MIN_STAT_VALUE: f32 = 0.0;
+const MAX_STAT_VALUE: f32 = 100.0;
+
+#[derive(SpacetimeType, Clone, Debug, PartialEq, Eq)]
+pub enum HungerTier {
+    WellFed,
+    Normal,
+    Hungry,
+    Starving,
+}
+
+#[derive(SpacetimeType, Clone, Debug, PartialEq, Eq)]
+pub enum ThirstTier {
+    Hydrated,
+    Normal,
+    Thirsty,
+    Dehydrated,
+}
+
+fn classify_hunger(hunger: f32) -> HungerTier {
+    if hunger >= HUNGER_WELL_FED_THRESHOLD {
+        HungerTier::WellFed
+    } else if hunger <= HUNGER_STARVING_THRESHOLD {
+        HungerTier::Starving
+    } else if hunger <= HUNGER_HUNGRY_THRESHOLD {
+        HungerTier::Hungry
+    } else {
+        HungerTier::Normal
+    }
+}
+
+fn classify_thirst(thirst: f32) -> ThirstTier {
+    if thirst >= THIRST_HYDRATED_THRESHOLD {
+        ThirstTier::Hydrated
+    } else if thirst <= THIRST_DEHYDRATED_THRESHOLD {
+        ThirstTier::Dehydrated
+    } else if thirst <= THIRST_THIRSTY_THRESHOLD {
+        ThirstTier::Thirsty
+    } else {
+        ThirstTier::Normal
+    }
+}
+
+/// Current hunger/thirst tier for a player, kept in its own table (rather than on
+/// `Player` directly) so the client can subscribe to it the same way it does other
+/// derived stat tables. Updated every `tick_survival_stats`.
+#[table(name = player_survival_state, public)]
+#[derive(Clone, Debug)]
+pub struct PlayerSurvivalState {
+    #[primary_key]
+    pub player_identity: Identity,
+    pub hunger_tier: HungerTier,
+    pub thirst_tier: ThirstTier,
+}
+
+#[table(name = survival_tick_schedule, scheduled(tick_survival_stats))]
+pub struct SurvivalTickSchedule {
+    #[primary_key]
+    #[auto_inc]
+    pub schedule_id: u64,
+    pub scheduled_at: ScheduleAt,
+}
+
+/// The module's single lifecycle `init` reducer. SpacetimeDB only runs one of these per
+/// module, so every one-time startup step - scheduling the recurring survival tick,
+/// seeding lookup tables, etc. - has to be called from here rather than getting its own.
+#[spacetimedb::reducer(init)]
+pub fn init_module(ctx: &ReducerContext) {
+    ctx.db.survival_tick_schedule().insert(SurvivalTickSchedule {
+        schedule_id: 0,
+        scheduled_at: TimeDuration::from_micros(TICK_INTERVAL_SECS * 1_000_000).into(),
+    });
+
+    seed_consumable_effect_stats(ctx);
+}
+
+/// Decays every player's hunger and thirst, classifies them into tiers, and applies
+/// the health consequences of those tiers (starvation/dehydration drain, well-fed regen).
+#[spacetimedb::reducer]
+pub fn tick_survival_stats(ctx: &ReducerContext, _args: SurvivalTickSchedule) -> Result<(), String> {
+    // Only the module's own scheduler may drive this; otherwise any client could call it
+    // directly in a loop and mass-drain every player's health/hunger/thirst.
+    if ctx.sender != ctx.identity() {
+        return Err("tick_survival_stats may only be invoked by the scheduler.".to_string());
+    }
+
+    let players = ctx.db.player();
+    let survival_states = ctx.db.player_survival_state();
+
+    for mut player in players.iter() {
+        player.hunger = (player.hunger - HUNGER_DECAY_PER_TICK).max(MIN_STAT_VALUE);
+        player.thirst = (player.thirst - THIRST_DECAY_PER_TICK).max(MIN_STAT_VALUE);
+
+        let hunger_tier = classify_hunger(player.hunger);
+        let thirst_tier = classify_thirst(player.thirst);
+
+        match hunger_tier {
+            HungerTier::Starving => {
+                player.health = (player.health - STARVATION_HEALTH_DRAIN_PER_TICK).max(MIN_STAT_VALUE);
+            }
+            HungerTier::WellFed => {
+                player.health = (player.health + WELL_FED_HEALTH_REGEN_PER_TICK).min(MAX_STAT_VALUE);
+            }
+            HungerTier::Normal | HungerTier::Hungry => {}
+        }
+        if thirst_tier == ThirstTier::Dehydrated {
+            player.health = (player.health - DEHYDRATION_HEALTH_DRAIN_PER_TICK).max(MIN_STAT_VALUE);
+        }
+
+        let player_identity = player.identity;
+        log::debug!(
+            "[SurvivalTick] Player {:?} now Hu {:.1} ({:?}), T {:.1} ({:?}), H {:.1}",
+            player_identity, player.hunger, hunger_tier, player.thirst, thirst_tier, player.health
+        );
+        players.identity().update(player);
+
+        match survival_states.player_identity().find(player_identity) {
+            Some(mut state) => {
+                state.hunger_tier = hunger_tier;
+                state.thirst_tier = thirst_tier;
+                survival_states.player_identity().update(state);
+            }
+            None => {
+                survival_states.insert(PlayerSurvivalState {
+                    player_identity,
+                    hunger_tier,
+                    thirst_tier,
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_hunger_tiers() {
+        assert_eq!(classify_hunger(100.0), HungerTier::WellFed);
+        assert_eq!(classify_hunger(80.0), HungerTier::WellFed);
+        assert_eq!(classify_hunger(79.9), HungerTier::Normal);
+        assert_eq!(classify_hunger(40.0), HungerTier::Hungry);
+        assert_eq!(classify_hunger(10.1), HungerTier::Hungry);
+        assert_eq!(classify_hunger(10.0), HungerTier::Starving);
+        assert_eq!(classify_hunger(0.0), HungerTier::Starving);
+    }
+
+    #[test]
+    fn classify_thirst_tiers() {
+        assert_eq!(classify_thirst(100.0), ThirstTier::Hydrated);
+        assert_eq!(classify_thirst(80.0), ThirstTier::Hydrated);
+        assert_eq!(classify_thirst(79.9), ThirstTier::Normal);
+        assert_eq!(classify_thirst(40.0), ThirstTier::Thirsty);
+        assert_eq!(classify_thirst(10.1), ThirstTier::Thirsty);
+        assert_eq!(classify_thirst(10.0), ThirstTier::Dehydrated);
+        assert_eq!(classify_thirst(0.0), ThirstTier::Dehydrated);
+    }
+}